@@ -0,0 +1,111 @@
+use crate::Grid;
+use crate::RGBA;
+
+#[derive(Clone, Copy, Debug)]
+pub enum BlendMode {
+    Over,
+    Multiply,
+    Screen,
+    Add,
+    Overlay,
+    Difference,
+}
+
+impl BlendMode {
+    #[inline(always)]
+    fn blend_channel(&self, src: f32, dst: f32) -> f32 {
+        match self {
+            BlendMode::Over => src,
+            BlendMode::Multiply => src * dst,
+            BlendMode::Screen => 1.0 - (1.0 - src) * (1.0 - dst),
+            BlendMode::Add => src + dst,
+            BlendMode::Overlay => {
+                if dst <= 0.5 {
+                    2.0 * src * dst
+                } else {
+                    1.0 - 2.0 * (1.0 - src) * (1.0 - dst)
+                }
+            }
+            BlendMode::Difference => (src - dst).abs(),
+        }
+    }
+}
+
+pub type Layer = (Grid<RGBA>, BlendMode, f32);
+
+// Folds `layers` bottom-to-top: each blend mode reshapes the color channels,
+// then the result is composited onto what came before via straight-alpha Over.
+#[inline(always)]
+fn blend_pixel(dst: RGBA, src: RGBA, mode: BlendMode, opacity: f32) -> RGBA {
+    let blended = RGBA {
+        r: mode.blend_channel(src.r, dst.r).clamp(0.0, 1.0),
+        g: mode.blend_channel(src.g, dst.g).clamp(0.0, 1.0),
+        b: mode.blend_channel(src.b, dst.b).clamp(0.0, 1.0),
+        a: src.a,
+    };
+    let src_a = blended.a * opacity;
+    RGBA {
+        r: blended.r * src_a + dst.r * (1.0 - src_a),
+        g: blended.g * src_a + dst.g * (1.0 - src_a),
+        b: blended.b * src_a + dst.b * (1.0 - src_a),
+        a: (src_a + dst.a * (1.0 - src_a)).clamp(0.0, 1.0),
+    }
+}
+
+pub fn composite(layers: &[Layer]) -> Grid<RGBA> {
+    assert!(!layers.is_empty(), "composite requires at least one layer");
+    let raster = layers[0].0.raster;
+    let mut result = Grid {
+        raster,
+        cells: vec![
+            RGBA {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+                a: 0.0,
+            };
+            raster.width * raster.height
+        ],
+    };
+
+    for (layer, mode, opacity) in layers {
+        for i in 0..result.cells.len() {
+            result.cells[i] = blend_pixel(result.cells[i], layer.cells[i], *mode, *opacity);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Raster;
+
+    fn single_pixel(color: RGBA) -> Grid<RGBA> {
+        Grid {
+            raster: Raster { width: 1, height: 1 },
+            cells: vec![color],
+        }
+    }
+
+    #[test]
+    fn screen_lightens_against_an_opaque_background() {
+        let background = single_pixel(RGBA { r: 0.2, g: 0.2, b: 0.2, a: 1.0 });
+        let foreground = single_pixel(RGBA { r: 0.5, g: 0.5, b: 0.5, a: 1.0 });
+        let result = composite(&[(background, BlendMode::Over, 1.0), (foreground, BlendMode::Screen, 1.0)]);
+
+        let expected = 1.0 - (1.0 - 0.5) * (1.0 - 0.2);
+        assert!((result.cells[0].r - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn multiply_darkens_against_an_opaque_background() {
+        let background = single_pixel(RGBA { r: 0.8, g: 0.8, b: 0.8, a: 1.0 });
+        let foreground = single_pixel(RGBA { r: 0.5, g: 0.5, b: 0.5, a: 1.0 });
+        let result = composite(&[(background, BlendMode::Over, 1.0), (foreground, BlendMode::Multiply, 1.0)]);
+
+        let expected = 0.5 * 0.8;
+        assert!((result.cells[0].r - expected).abs() < 1e-6);
+    }
+}
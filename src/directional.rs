@@ -0,0 +1,191 @@
+use crate::grid::Boundary;
+use crate::grid::Convolver;
+use crate::grid::Grid;
+use crate::grid::RGBA;
+use crate::kernel::Kernel;
+use rayon::prelude::*;
+
+const DIRECTIONS: usize = 8;
+
+#[derive(Clone, Copy, Debug)]
+pub struct DirectionalConvolver {
+    pub primary_strength: f32,
+    pub secondary_strength: f32,
+    pub damping: f32,
+}
+
+impl DirectionalConvolver {
+    #[inline(always)]
+    fn direction_vector(index: usize) -> (f32, f32) {
+        let angle = std::f32::consts::PI * index as f32 / DIRECTIONS as f32;
+        (angle.cos(), angle.sin())
+    }
+
+    fn sample_line(
+        grid: &Grid<RGBA>,
+        x: usize,
+        y: usize,
+        dir: (f32, f32),
+        radius: isize,
+        boundary: Boundary,
+    ) -> Vec<RGBA> {
+        let mut taps = Vec::with_capacity((2 * radius + 1) as usize);
+        for t in -radius..=radius {
+            if t == 0 {
+                continue;
+            }
+            let dx = (t as f32 * dir.0).round() as isize;
+            let dy = (t as f32 * dir.1).round() as isize;
+            if let Some((sx, sy)) = grid.raster.resolve(x as isize + dx, y as isize + dy, boundary) {
+                taps.push(*grid.get(sx, sy));
+            }
+        }
+        taps
+    }
+
+    // Lower is better: pixels along the true edge direction vary the least.
+    fn dispersion(taps: &[RGBA]) -> f32 {
+        if taps.is_empty() {
+            return f32::MAX;
+        }
+        let lumas: Vec<f32> = taps.iter().map(|c| (c.r + c.g + c.b) / 3.0).collect();
+        let mean = lumas.iter().sum::<f32>() / lumas.len() as f32;
+        lumas.iter().map(|l| (l - mean) * (l - mean)).sum::<f32>() / lumas.len() as f32
+    }
+
+    fn blend(center: RGBA, taps: &[RGBA], strength: f32, damping: f32) -> RGBA {
+        if taps.is_empty() {
+            return center;
+        }
+        let n = taps.len() as f32;
+        let avg_r = taps.iter().map(|c| c.r).sum::<f32>() / n;
+        let avg_g = taps.iter().map(|c| c.g).sum::<f32>() / n;
+        let avg_b = taps.iter().map(|c| c.b).sum::<f32>() / n;
+        let avg_a = taps.iter().map(|c| c.a).sum::<f32>() / n;
+
+        let amount = (strength * damping).clamp(0.0, 1.0);
+        RGBA {
+            r: clamp_to_range(center.r + amount * (avg_r - center.r), taps, |c| c.r),
+            g: clamp_to_range(center.g + amount * (avg_g - center.g), taps, |c| c.g),
+            b: clamp_to_range(center.b + amount * (avg_b - center.b), taps, |c| c.b),
+            a: clamp_to_range(center.a + amount * (avg_a - center.a), taps, |c| c.a),
+        }
+    }
+}
+
+// Never lets an adjustment push a channel past the range its own taps span.
+#[inline(always)]
+fn clamp_to_range(value: f32, taps: &[RGBA], channel: impl Fn(&RGBA) -> f32) -> f32 {
+    let min = taps.iter().map(|c| channel(c)).fold(f32::MAX, f32::min);
+    let max = taps.iter().map(|c| channel(c)).fold(f32::MIN, f32::max);
+    value.clamp(min, max)
+}
+
+impl Convolver<RGBA> for DirectionalConvolver {
+    #[inline(always)]
+    fn convolve(&self, grid: &mut Grid<RGBA>, kernel: &Kernel, boundary: Boundary) {
+        let width = grid.width();
+        let height = grid.height();
+        let radius = (kernel.width().max(kernel.height()) / 2).max(1) as isize;
+        let mut new_cells = grid.cells.clone();
+
+        new_cells
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for x in 0..width {
+                    let center = *grid.get(x, y);
+
+                    let mut best_dir = 0;
+                    let mut best_score = f32::MAX;
+                    for dir_index in 0..DIRECTIONS {
+                        let dir = Self::direction_vector(dir_index);
+                        let taps = Self::sample_line(grid, x, y, dir, radius, boundary);
+                        let score = Self::dispersion(&taps);
+                        if score < best_score {
+                            best_score = score;
+                            best_dir = dir_index;
+                        }
+                    }
+
+                    let primary_taps =
+                        Self::sample_line(grid, x, y, Self::direction_vector(best_dir), radius, boundary);
+                    let primary = Self::blend(center, &primary_taps, self.primary_strength, self.damping);
+
+                    // The two directions 45° either side of the dominant edge act as
+                    // a weaker secondary smoothing pass, same as CDEF's secondary taps.
+                    let mut secondary_taps = Self::sample_line(
+                        grid,
+                        x,
+                        y,
+                        Self::direction_vector((best_dir + 2) % DIRECTIONS),
+                        radius,
+                        boundary,
+                    );
+                    secondary_taps.extend(Self::sample_line(
+                        grid,
+                        x,
+                        y,
+                        Self::direction_vector((best_dir + DIRECTIONS - 2) % DIRECTIONS),
+                        radius,
+                        boundary,
+                    ));
+                    row[x] = Self::blend(primary, &secondary_taps, self.secondary_strength, self.damping);
+                }
+            });
+
+        grid.cells = new_cells;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flat_grid(width: usize, height: usize, color: RGBA) -> Grid<RGBA> {
+        Grid {
+            raster: crate::grid::Raster { width, height },
+            cells: vec![color; width * height],
+        }
+    }
+
+    #[test]
+    fn uniform_grid_is_left_unchanged() {
+        let color = RGBA {
+            r: 0.4,
+            g: 0.4,
+            b: 0.4,
+            a: 1.0,
+        };
+        let mut grid = flat_grid(8, 8, color);
+        let convolver = DirectionalConvolver {
+            primary_strength: 1.0,
+            secondary_strength: 1.0,
+            damping: 1.0,
+        };
+        convolver.convolve(&mut grid, &Kernel::gauss3(), Boundary::Wrap);
+
+        for cell in &grid.cells {
+            assert!((cell.r - color.r).abs() < 1e-6);
+            assert!((cell.g - color.g).abs() < 1e-6);
+            assert!((cell.b - color.b).abs() < 1e-6);
+            assert!((cell.a - color.a).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn blend_never_overshoots_the_taps_span() {
+        let center = RGBA {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+            a: 1.0,
+        };
+        let taps = vec![
+            RGBA { r: 0.0, g: 0.0, b: 0.0, a: 0.0 },
+            RGBA { r: 0.2, g: 0.2, b: 0.2, a: 0.2 },
+        ];
+        let blended = DirectionalConvolver::blend(center, &taps, 1.0, 1.0);
+        assert!(blended.r <= 0.2 + 1e-6);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::grid::{Boundary, Convolver, Grid, NoiseParams, RGBA};
+use crate::kernel::Kernel;
+use crate::lenia::{Integrator, Lenia, LeniaParams};
+use crate::rgba::SimdConvolver;
+use std::slice;
+
+pub struct GridHandle {
+    grid: Grid<RGBA>,
+}
+
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum CaError {
+    Ok = 0,
+    NullHandle = 1,
+    BufferTooSmall = 2,
+    InvalidArgument = 3,
+}
+
+#[no_mangle]
+pub extern "C" fn ca_grid_new(width: usize, height: usize, seed: u64) -> *mut GridHandle {
+    if width == 0 || height == 0 {
+        return std::ptr::null_mut();
+    }
+    let grid = Grid::new_random_seeded(width, height, seed);
+    Box::into_raw(Box::new(GridHandle { grid }))
+}
+
+#[no_mangle]
+pub extern "C" fn ca_grid_new_noise(
+    width: usize,
+    height: usize,
+    seed: u32,
+    frequency: f64,
+    octaves: u32,
+    lacunarity: f64,
+    persistence: f64,
+) -> *mut GridHandle {
+    if width == 0 || height == 0 {
+        return std::ptr::null_mut();
+    }
+    let params = NoiseParams {
+        seed,
+        frequency,
+        octaves,
+        lacunarity,
+        persistence,
+        channel_seeds: None,
+    };
+    let grid = Grid::new_noise(width, height, params);
+    Box::into_raw(Box::new(GridHandle { grid }))
+}
+
+// kernel_id: 0 = gauss3, 1 = gauss5, 2 = gauss7.
+// convolver_id: 0 = plain convolve, 1 = rayon-chunked convolve, 2 = Lenia Euler step,
+// 3 = SIMD-packed convolve.
+// Boundary is fixed to wrap (the toroidal topology the rest of the engine defaults to).
+#[no_mangle]
+pub extern "C" fn ca_grid_step(
+    handle: *mut GridHandle,
+    kernel_id: i32,
+    convolver_id: i32,
+) -> CaError {
+    let Some(handle) = (unsafe { handle.as_mut() }) else {
+        return CaError::NullHandle;
+    };
+    let kernel = match kernel_id {
+        0 => Kernel::gauss3(),
+        1 => Kernel::gauss5(),
+        2 => Kernel::gauss7(),
+        _ => return CaError::InvalidArgument,
+    };
+    match convolver_id {
+        0 => handle.grid.convolve(&kernel, Boundary::Wrap),
+        1 => handle.grid.convolve_par(&kernel, Boundary::Wrap),
+        2 => {
+            let lenia = Lenia::new(
+                kernel,
+                LeniaParams {
+                    mu: 0.15,
+                    sigma: 0.015,
+                    dt: 0.1,
+                },
+                Integrator::Euler,
+                Boundary::Wrap,
+            );
+            lenia.step(&mut handle.grid);
+        }
+        3 => SimdConvolver.convolve(&mut handle.grid, &kernel, Boundary::Wrap),
+        _ => return CaError::InvalidArgument,
+    }
+    CaError::Ok
+}
+
+#[no_mangle]
+pub extern "C" fn ca_grid_get_pixels(
+    handle: *const GridHandle,
+    out_ptr: *mut u8,
+    out_len: usize,
+) -> CaError {
+    let Some(handle) = (unsafe { handle.as_ref() }) else {
+        return CaError::NullHandle;
+    };
+    if out_ptr.is_null() {
+        return CaError::NullHandle;
+    }
+    let required = handle.grid.cells.len() * 4;
+    if out_len < required {
+        return CaError::BufferTooSmall;
+    }
+    let out = unsafe { slice::from_raw_parts_mut(out_ptr, required) };
+    for (i, cell) in handle.grid.cells.iter().enumerate() {
+        out[i * 4] = (cell.r * 255.0) as u8;
+        out[i * 4 + 1] = (cell.g * 255.0) as u8;
+        out[i * 4 + 2] = (cell.b * 255.0) as u8;
+        out[i * 4 + 3] = (cell.a * 255.0) as u8;
+    }
+    CaError::Ok
+}
+
+#[no_mangle]
+pub extern "C" fn ca_grid_free(handle: *mut GridHandle) {
+    if !handle.is_null() {
+        unsafe {
+            drop(Box::from_raw(handle));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_pixels_reports_buffer_too_small() {
+        let handle = ca_grid_new(2, 2, 0);
+        let mut buffer = [0u8; 4];
+        let err = ca_grid_get_pixels(handle, buffer.as_mut_ptr(), buffer.len());
+        assert_eq!(err, CaError::BufferTooSmall);
+        ca_grid_free(handle);
+    }
+
+    #[test]
+    fn get_pixels_rejects_a_null_out_ptr() {
+        let handle = ca_grid_new(2, 2, 0);
+        let err = ca_grid_get_pixels(handle, std::ptr::null_mut(), 16);
+        assert_eq!(err, CaError::NullHandle);
+        ca_grid_free(handle);
+    }
+
+    #[test]
+    fn get_pixels_rejects_a_null_handle() {
+        let mut buffer = [0u8; 16];
+        let err = ca_grid_get_pixels(std::ptr::null(), buffer.as_mut_ptr(), buffer.len());
+        assert_eq!(err, CaError::NullHandle);
+    }
+
+    #[test]
+    fn get_pixels_succeeds_with_an_exact_buffer() {
+        let handle = ca_grid_new(2, 2, 0);
+        let mut buffer = [0u8; 16];
+        let err = ca_grid_get_pixels(handle, buffer.as_mut_ptr(), buffer.len());
+        assert_eq!(err, CaError::Ok);
+        ca_grid_free(handle);
+    }
+}
@@ -1,9 +1,9 @@
 use crate::kernel::Kernel;
+use noise::{NoiseFn, OpenSimplex, Seedable};
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 use rayon::prelude::*;
 use rustfft::{num_complex::Complex, num_traits::Zero, FftPlanner};
-use std::time::Instant;
 
 #[derive(Clone, Copy, Debug)]
 pub struct RGBA {
@@ -13,7 +13,7 @@ pub struct RGBA {
     pub a: f32,
 }
 impl RGBA {
-    const ZERO: RGBA = RGBA {
+    pub(crate) const ZERO: RGBA = RGBA {
         r: 0.0,
         g: 0.0,
         b: 0.0,
@@ -26,6 +26,41 @@ pub struct Center {
     pub y: usize,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Boundary {
+    Zero,
+    Clamp,
+    Wrap,
+    Mirror,
+}
+
+impl Boundary {
+    #[inline(always)]
+    fn resolve(&self, coord: isize, len: usize) -> Option<usize> {
+        match self {
+            Boundary::Zero => {
+                if coord >= 0 && coord < len as isize {
+                    Some(coord as usize)
+                } else {
+                    None
+                }
+            }
+            Boundary::Clamp => Some(coord.clamp(0, len as isize - 1) as usize),
+            Boundary::Wrap => Some(coord.rem_euclid(len as isize) as usize),
+            Boundary::Mirror => {
+                let period = 2 * len as isize;
+                let wrapped = coord.rem_euclid(period);
+                let mirrored = if wrapped >= len as isize {
+                    period - 1 - wrapped
+                } else {
+                    wrapped
+                };
+                Some(mirrored as usize)
+            }
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct Raster {
     pub width: usize,
@@ -59,6 +94,13 @@ impl Raster {
             y: self.height / 2,
         }
     }
+
+    #[inline(always)]
+    pub fn resolve(&self, x: isize, y: isize, boundary: Boundary) -> Option<(usize, usize)> {
+        let rx = boundary.resolve(x, self.width)?;
+        let ry = boundary.resolve(y, self.height)?;
+        Some((rx, ry))
+    }
 }
 
 #[derive(Clone)]
@@ -116,6 +158,7 @@ impl<Cell> Grid<Cell> {
         kernel: &Kernel,
         x: usize,
         y: usize,
+        boundary: Boundary,
         mut f: impl FnMut(usize, usize, usize, &Cell, &f32),
     ) {
         let kc = kernel.center();
@@ -123,11 +166,11 @@ impl<Cell> Grid<Cell> {
             for kx in 0..kernel.width() {
                 let dx = x as isize + kx as isize - kc.x as isize;
                 let dy = y as isize + ky as isize - kc.y as isize;
-                if !self.raster.is_inside(dx, dy) {
+                let Some((rx, ry)) = self.raster.resolve(dx, dy, boundary) else {
                     continue;
-                }
-                let index = self.index(dx as usize, dy as usize);
-                let cell = self.get(dx as usize, dy as usize);
+                };
+                let index = self.index(rx, ry);
+                let cell = self.get(rx, ry);
                 let weight = kernel.get(kx, ky);
                 f(kx, ky, index, cell, weight);
             }
@@ -146,17 +189,25 @@ impl<Cell> Grid<Cell> {
     }
 }
 
+pub trait Convolver<Cell> {
+    fn convolve(&self, grid: &mut Grid<Cell>, kernel: &Kernel, boundary: Boundary);
+}
+
+pub struct SimpleConvolver;
+pub struct ParConvolver;
+pub struct FftConvolver;
+
 macro_rules! convolve_kernel {
-    ($self:expr, $kernel:expr, $x:expr, $y:expr, $new_cell:expr) => {{
+    ($self:expr, $kernel:expr, $x:expr, $y:expr, $boundary:expr, $new_cell:expr) => {{
         let kc = $kernel.center();
         for ky in 0..$kernel.height() {
             for kx in 0..$kernel.width() {
                 let dx = $x as isize + kx as isize - kc.x as isize;
                 let dy = $y as isize + ky as isize - kc.y as isize;
-                if !$self.raster.is_inside(dx, dy) {
+                let Some((rx, ry)) = $self.raster.resolve(dx, dy, $boundary) else {
                     continue;
-                }
-                let neighbour = $self.get(dx as usize, dy as usize);
+                };
+                let neighbour = $self.get(rx, ry);
                 let weight = $kernel.cells[ky * $kernel.width() + kx];
                 $new_cell.r += neighbour.r * weight;
                 $new_cell.g += neighbour.g * weight;
@@ -168,52 +219,127 @@ macro_rules! convolve_kernel {
 }
 
 impl Grid<RGBA> {
-    pub fn convolve_fft(&mut self, kernel: &Kernel) {
+    pub fn convolve_fft(&mut self, kernel: &Kernel, boundary: Boundary) {
+        match boundary {
+            // A kernel the size of the grid with circular indexing gives true
+            // wrap-around convolution without any padding.
+            Boundary::Wrap => self.convolve_fft_wrap(kernel),
+            _ => self.convolve_fft_padded(kernel, boundary),
+        }
+    }
+
+    fn convolve_fft_wrap(&mut self, kernel: &Kernel) {
+        let (width, height) = (self.width(), self.height());
+        let size = width * height;
+        let mut planner = FftPlanner::new();
+        let fft = planner.plan_fft_forward(size);
+        let ifft = planner.plan_fft_inverse(size);
+
+        let mut grid_r = vec![Complex::zero(); size];
+        let mut grid_g = vec![Complex::zero(); size];
+        let mut grid_b = vec![Complex::zero(); size];
+        let mut grid_a = vec![Complex::zero(); size];
+        let mut kernel_complex = vec![Complex::zero(); size];
+
+        self.for_each_cell(|x, y, i, color| {
+            let _ = (x, y);
+            grid_r[i] = Complex::new(color.r, 0.0);
+            grid_g[i] = Complex::new(color.g, 0.0);
+            grid_b[i] = Complex::new(color.b, 0.0);
+            grid_a[i] = Complex::new(color.a, 0.0);
+        });
+
+        let kc = kernel.center();
+        kernel.for_each_cell(|kx, ky, _, weight| {
+            let ox = (kx as isize - kc.x as isize).rem_euclid(width as isize) as usize;
+            let oy = (ky as isize - kc.y as isize).rem_euclid(height as isize) as usize;
+            kernel_complex[oy * width + ox] += Complex::new(*weight, 0.0);
+        });
+
+        fft.process(&mut grid_r);
+        fft.process(&mut grid_g);
+        fft.process(&mut grid_b);
+        fft.process(&mut grid_a);
+        fft.process(&mut kernel_complex);
+
+        for i in 0..size {
+            grid_r[i] *= kernel_complex[i];
+            grid_g[i] *= kernel_complex[i];
+            grid_b[i] *= kernel_complex[i];
+            grid_a[i] *= kernel_complex[i];
+        }
+
+        ifft.process(&mut grid_r);
+        ifft.process(&mut grid_g);
+        ifft.process(&mut grid_b);
+        ifft.process(&mut grid_a);
+
+        self.for_each_cell_mut(|_, _, i, cell| {
+            cell.r = (grid_r[i].re / size as f32).clamp(0.0, 1.0);
+            cell.g = (grid_g[i].re / size as f32).clamp(0.0, 1.0);
+            cell.b = (grid_b[i].re / size as f32).clamp(0.0, 1.0);
+            cell.a = (grid_a[i].re / size as f32).clamp(0.0, 1.0);
+        });
+    }
+
+    fn convolve_fft_padded(&mut self, kernel: &Kernel, boundary: Boundary) {
         let (grid_width, grid_height) = (self.width(), self.height());
         let (kernel_width, kernel_height) = (kernel.width(), kernel.height());
         let padded_width = grid_width + kernel_width - 1;
         let padded_height = grid_height + kernel_height - 1;
-        let time = Instant::now();
         let mut planner = FftPlanner::new();
-        println!("Planner took {:?}", time.elapsed());
-        let time = Instant::now();
         let fft = planner.plan_fft_forward(padded_width * padded_height);
-        println!("FFT forward took {:?}", time.elapsed());
-        let time = Instant::now();
         let ifft = planner.plan_fft_inverse(padded_width * padded_height);
-        println!("FFT inverse took {:?}", time.elapsed());
 
         let padded_raster = Raster {
             width: padded_width,
             height: padded_height,
         };
 
-        let time = Instant::now();
         let mut grid_r = vec![Complex::zero(); padded_width * padded_height];
         let mut grid_g = vec![Complex::zero(); padded_width * padded_height];
         let mut grid_b = vec![Complex::zero(); padded_width * padded_height];
         let mut grid_a = vec![Complex::zero(); padded_width * padded_height];
         let mut kernel_complex = vec![Complex::zero(); padded_width * padded_height];
 
-        self.for_each_cell(|x, y, _, color| {
-            let padded_index = y * padded_width + x;
-            grid_r[padded_index] = Complex::new(color.r, 0.0);
-            grid_g[padded_index] = Complex::new(color.g, 0.0);
-            grid_b[padded_index] = Complex::new(color.b, 0.0);
-            grid_a[padded_index] = Complex::new(color.a, 0.0);
-        });
+        // Outside the grid proper, fall back to the chosen boundary so the
+        // padding margin agrees with the spatial convolvers; Zero just
+        // leaves it at the default Complex::zero(). The flattened circular
+        // FFT reads taps left of column 0 from the end of the previous padded
+        // row and taps above row 0 from the last padded rows, so those margin
+        // cells are filled from the (negative) coordinates they stand in for.
+        let margin = kernel.center();
+        for py in 0..padded_height {
+            for px in 0..padded_width {
+                let padded_index = py * padded_width + px;
+                let (ax, row) = if px >= grid_width + margin.x {
+                    (px as isize - padded_width as isize, py + 1)
+                } else {
+                    (px as isize, py)
+                };
+                let ay = if row >= grid_height + margin.y {
+                    row as isize - padded_height as isize
+                } else {
+                    row as isize
+                };
+                if let Some((sx, sy)) = self.raster.resolve(ax, ay, boundary) {
+                    let color = self.get(sx, sy);
+                    grid_r[padded_index] = Complex::new(color.r, 0.0);
+                    grid_g[padded_index] = Complex::new(color.g, 0.0);
+                    grid_b[padded_index] = Complex::new(color.b, 0.0);
+                    grid_a[padded_index] = Complex::new(color.a, 0.0);
+                }
+            }
+        }
         kernel.for_each_cell(|x, y, _, cell| {
             let padded_index = y * padded_width + x;
             kernel_complex[padded_index] = Complex::new(*cell, 0.0);
         });
-        println!("Preparation took {:?}", time.elapsed());
-        let time = Instant::now();
         fft.process(&mut grid_r);
         fft.process(&mut grid_g);
         fft.process(&mut grid_b);
         fft.process(&mut grid_a);
         fft.process(&mut kernel_complex);
-        println!("FFT processing took {:?}", time.elapsed());
 
         padded_raster.for_each(|_, _, i| {
             grid_r[i] *= kernel_complex[i];
@@ -222,12 +348,10 @@ impl Grid<RGBA> {
             grid_a[i] *= kernel_complex[i];
         });
 
-        let time = Instant::now();
         ifft.process(&mut grid_r);
         ifft.process(&mut grid_g);
         ifft.process(&mut grid_b);
         ifft.process(&mut grid_a);
-        println!("IFFT processing took {:?}", time.elapsed());
 
         let kc = kernel.center();
         self.for_each_cell_mut(|x, y, _, cell| {
@@ -246,7 +370,7 @@ impl Grid<RGBA> {
         });
     }
 
-    pub fn convolve_par(&mut self, kernel: &Kernel) {
+    pub fn convolve_par(&mut self, kernel: &Kernel, boundary: Boundary) {
         let width = self.width();
         let height = self.height();
         let mut new_cells = vec![RGBA::ZERO; width * height];
@@ -258,7 +382,7 @@ impl Grid<RGBA> {
                 for x in 0..width {
                     let mut new_cell = RGBA::ZERO;
 
-                    convolve_kernel!(self, kernel, x, y, new_cell);
+                    convolve_kernel!(self, kernel, x, y, boundary, new_cell);
 
                     row[x] = RGBA {
                         r: new_cell.r.clamp(0.0, 1.0),
@@ -272,14 +396,14 @@ impl Grid<RGBA> {
         self.cells = new_cells;
     }
 
-    pub fn convolve(&mut self, kernel: &Kernel) {
+    pub fn convolve(&mut self, kernel: &Kernel, boundary: Boundary) {
         let width = self.width();
         let height = self.height();
         let mut new_cells = vec![RGBA::ZERO; width * height];
         for y in 0..height {
             for x in 0..width {
                 let mut new_cell = RGBA::ZERO;
-                convolve_kernel!(self, kernel, x, y, new_cell);
+                convolve_kernel!(self, kernel, x, y, boundary, new_cell);
                 let index = self.index(x, y);
                 new_cells[index] = new_cell;
             }
@@ -288,7 +412,13 @@ impl Grid<RGBA> {
     }
 
     pub fn new_random(width: usize, height: usize) -> Grid<RGBA> {
-        let mut rng = ChaCha8Rng::from_seed([0; 32]);
+        Self::new_random_seeded(width, height, 0)
+    }
+
+    pub fn new_random_seeded(width: usize, height: usize, seed: u64) -> Grid<RGBA> {
+        let mut seed_bytes = [0u8; 32];
+        seed_bytes[..8].copy_from_slice(&seed.to_le_bytes());
+        let mut rng = ChaCha8Rng::from_seed(seed_bytes);
         let mut grid = Grid {
             raster: Raster { width, height },
             cells: vec![RGBA::ZERO; width * height],
@@ -303,4 +433,123 @@ impl Grid<RGBA> {
 
         grid
     }
+
+    pub fn new_noise(width: usize, height: usize, params: NoiseParams) -> Grid<RGBA> {
+        // Without explicit per-channel seeds there is only one noise field to sample,
+        // so alpha defaults to fully opaque rather than silently tracking luma.
+        let alpha_seed = params.channel_seeds.map(|seeds| seeds[3]);
+        let seeds = params
+            .channel_seeds
+            .map(|s| [s[0], s[1], s[2]])
+            .unwrap_or([params.seed; 3]);
+        let generators = seeds.map(|seed| OpenSimplex::new(0).set_seed(seed));
+        let alpha_generator = alpha_seed.map(|seed| OpenSimplex::new(0).set_seed(seed));
+        let mut grid = Grid {
+            raster: Raster { width, height },
+            cells: vec![RGBA::ZERO; width * height],
+        };
+
+        grid.for_each_cell_mut(|x, y, _, cell| {
+            cell.r = fractal_noise(&generators[0], x, y, &params);
+            cell.g = fractal_noise(&generators[1], x, y, &params);
+            cell.b = fractal_noise(&generators[2], x, y, &params);
+            cell.a = match &alpha_generator {
+                Some(generator) => fractal_noise(generator, x, y, &params),
+                None => 1.0,
+            };
+        });
+
+        grid
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct NoiseParams {
+    pub seed: u32,
+    pub frequency: f64,
+    pub octaves: u32,
+    pub lacunarity: f64,
+    pub persistence: f64,
+    pub channel_seeds: Option<[u32; 4]>,
+}
+
+// Sums octaves of OpenSimplex noise and normalizes the result into [0,1].
+fn fractal_noise(generator: &OpenSimplex, x: usize, y: usize, params: &NoiseParams) -> f32 {
+    let mut amplitude = 1.0;
+    let mut frequency = params.frequency;
+    let mut sum = 0.0;
+    let mut norm = 0.0;
+    for _ in 0..params.octaves {
+        let value = generator.get([x as f64 * frequency, y as f64 * frequency]);
+        sum += value * amplitude;
+        norm += amplitude;
+        amplitude *= params.persistence;
+        frequency *= params.lacunarity;
+    }
+    (((sum / norm) + 1.0) / 2.0) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_resolves_out_of_range_coords() {
+        let raster = Raster { width: 4, height: 4 };
+        assert_eq!(raster.resolve(-1, 0, Boundary::Wrap), Some((3, 0)));
+        assert_eq!(raster.resolve(4, 0, Boundary::Wrap), Some((0, 0)));
+    }
+
+    #[test]
+    fn clamp_resolves_to_the_nearest_edge() {
+        let raster = Raster { width: 4, height: 4 };
+        assert_eq!(raster.resolve(-5, 10, Boundary::Clamp), Some((0, 3)));
+    }
+
+    #[test]
+    fn mirror_reflects_at_the_edge() {
+        let raster = Raster { width: 4, height: 4 };
+        assert_eq!(raster.resolve(-1, 0, Boundary::Mirror), Some((0, 0)));
+        assert_eq!(raster.resolve(4, 0, Boundary::Mirror), Some((3, 0)));
+    }
+
+    #[test]
+    fn zero_boundary_rejects_out_of_range_coords() {
+        let raster = Raster { width: 4, height: 4 };
+        assert_eq!(raster.resolve(-1, 0, Boundary::Zero), None);
+        assert_eq!(raster.resolve(0, 0, Boundary::Zero), Some((0, 0)));
+    }
+
+    #[test]
+    fn new_noise_defaults_alpha_to_opaque() {
+        let params = NoiseParams {
+            seed: 7,
+            frequency: 0.1,
+            octaves: 2,
+            lacunarity: 2.0,
+            persistence: 0.5,
+            channel_seeds: None,
+        };
+        let grid = Grid::new_noise(8, 8, params);
+        assert!(grid.cells.iter().all(|cell| cell.a == 1.0));
+    }
+
+    #[test]
+    fn new_noise_is_deterministic_for_a_given_seed() {
+        let params = NoiseParams {
+            seed: 42,
+            frequency: 0.1,
+            octaves: 3,
+            lacunarity: 2.0,
+            persistence: 0.5,
+            channel_seeds: None,
+        };
+        let a = Grid::new_noise(8, 8, params);
+        let b = Grid::new_noise(8, 8, params);
+        for (ca, cb) in a.cells.iter().zip(b.cells.iter()) {
+            assert_eq!(ca.r, cb.r);
+            assert_eq!(ca.g, cb.g);
+            assert_eq!(ca.b, cb.b);
+        }
+    }
 }
@@ -0,0 +1,130 @@
+use crate::grid::Boundary;
+use crate::grid::Grid;
+use crate::grid::RGBA;
+use crate::kernel::Kernel;
+
+#[derive(Clone, Copy, Debug)]
+pub struct LeniaParams {
+    pub mu: f32,
+    pub sigma: f32,
+    pub dt: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Integrator {
+    Euler,
+    Rk4,
+}
+
+#[inline(always)]
+fn growth(u: f32, mu: f32, sigma: f32) -> f32 {
+    2.0 * (-((u - mu) * (u - mu)) / (2.0 * sigma * sigma)).exp() - 1.0
+}
+
+pub struct Lenia {
+    pub kernel: Kernel,
+    pub params: LeniaParams,
+    pub integrator: Integrator,
+    pub boundary: Boundary,
+}
+
+impl Lenia {
+    pub fn new(kernel: Kernel, params: LeniaParams, integrator: Integrator, boundary: Boundary) -> Lenia {
+        Lenia {
+            kernel,
+            params,
+            integrator,
+            boundary,
+        }
+    }
+
+    pub fn step(&self, grid: &mut Grid<RGBA>) {
+        match self.integrator {
+            Integrator::Euler => self.step_euler(grid),
+            Integrator::Rk4 => self.step_rk4(grid),
+        }
+    }
+
+    // Evaluates G(convolve(state, K)) at every cell, i.e. da/dt.
+    fn derivative(&self, state: &Grid<RGBA>) -> Vec<RGBA> {
+        let mut potential = state.clone();
+        potential.convolve(&self.kernel, self.boundary);
+        let (mu, sigma) = (self.params.mu, self.params.sigma);
+        potential
+            .cells
+            .iter()
+            .map(|u| RGBA {
+                r: growth(u.r, mu, sigma),
+                g: growth(u.g, mu, sigma),
+                b: growth(u.b, mu, sigma),
+                a: growth(u.a, mu, sigma),
+            })
+            .collect()
+    }
+
+    fn step_euler(&self, grid: &mut Grid<RGBA>) {
+        let dt = self.params.dt;
+        let derivative = self.derivative(grid);
+        for (cell, d) in grid.cells.iter_mut().zip(derivative.iter()) {
+            cell.r = (cell.r + dt * d.r).clamp(0.0, 1.0);
+            cell.g = (cell.g + dt * d.g).clamp(0.0, 1.0);
+            cell.b = (cell.b + dt * d.b).clamp(0.0, 1.0);
+            cell.a = (cell.a + dt * d.a).clamp(0.0, 1.0);
+        }
+    }
+
+    fn step_rk4(&self, grid: &mut Grid<RGBA>) {
+        let dt = self.params.dt;
+
+        let k1 = self.derivative(grid);
+        let mut stage = grid.clone();
+        advance(&mut stage, &k1, dt * 0.5);
+        let k2 = self.derivative(&stage);
+
+        let mut stage = grid.clone();
+        advance(&mut stage, &k2, dt * 0.5);
+        let k3 = self.derivative(&stage);
+
+        let mut stage = grid.clone();
+        advance(&mut stage, &k3, dt);
+        let k4 = self.derivative(&stage);
+
+        for i in 0..grid.cells.len() {
+            let cell = &mut grid.cells[i];
+            cell.r = combine_rk4(cell.r, k1[i].r, k2[i].r, k3[i].r, k4[i].r, dt);
+            cell.g = combine_rk4(cell.g, k1[i].g, k2[i].g, k3[i].g, k4[i].g, dt);
+            cell.b = combine_rk4(cell.b, k1[i].b, k2[i].b, k3[i].b, k4[i].b, dt);
+            cell.a = combine_rk4(cell.a, k1[i].a, k2[i].a, k3[i].a, k4[i].a, dt);
+        }
+    }
+}
+
+#[inline(always)]
+fn advance(grid: &mut Grid<RGBA>, derivative: &[RGBA], scale: f32) {
+    for (cell, d) in grid.cells.iter_mut().zip(derivative.iter()) {
+        cell.r = (cell.r + scale * d.r).clamp(0.0, 1.0);
+        cell.g = (cell.g + scale * d.g).clamp(0.0, 1.0);
+        cell.b = (cell.b + scale * d.b).clamp(0.0, 1.0);
+        cell.a = (cell.a + scale * d.a).clamp(0.0, 1.0);
+    }
+}
+
+#[inline(always)]
+fn combine_rk4(a: f32, k1: f32, k2: f32, k3: f32, k4: f32, dt: f32) -> f32 {
+    (a + dt * (k1 + 2.0 * k2 + 2.0 * k3 + k4) / 6.0).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn growth_peaks_at_mu() {
+        assert!((growth(0.15, 0.15, 0.015) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn growth_falls_off_away_from_mu() {
+        assert!(growth(0.5, 0.15, 0.015) < growth(0.2, 0.15, 0.015));
+    }
+}
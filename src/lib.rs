@@ -0,0 +1,15 @@
+#[macro_use]
+pub mod grid;
+pub mod compositor;
+pub mod directional;
+pub mod ffi;
+pub mod kernel;
+pub mod lenia;
+pub mod rgba;
+
+pub use compositor::*;
+pub use directional::*;
+pub use grid::*;
+pub use kernel::*;
+pub use lenia::*;
+pub use rgba::*;
@@ -1,6 +1,9 @@
 #[macro_use]
 mod grid;
+mod compositor;
+mod directional;
 mod kernel;
+mod lenia;
 mod rgba;
 
 use bevy::{
@@ -11,8 +14,10 @@ use bevy::{
     },
     sprite::*,
 };
+use compositor::*;
 use image::ImageBuffer;
 use kernel::*;
+use lenia::*;
 use rgba::*;
 use std::time::Instant;
 
@@ -25,9 +30,22 @@ const GRID_DIMENSIONS: (usize, usize) = (512, 512);
 #[derive(Resource)]
 struct World {
     grid: Grid<RGBA>,
+    background: Grid<RGBA>,
+    lenia: Lenia,
     entity: Option<Entity>,
 }
 
+impl World {
+    // The displayed image is the evolving grid screened over a static noise
+    // backdrop, so different Lenia species can eventually run on separate layers.
+    fn composited(&self) -> Grid<RGBA> {
+        composite(&[
+            (self.background.clone(), BlendMode::Over, 1.0),
+            (self.grid.clone(), BlendMode::Screen, 0.6),
+        ])
+    }
+}
+
 fn create_grid_texture(
     grid: &Grid<RGBA>,
     meshes: &mut ResMut<Assets<Mesh>>,
@@ -99,17 +117,45 @@ fn setup(
 ) {
     commands.spawn(Camera2dBundle::default());
     let grid = Grid::new_random(GRID_DIMENSIONS.0, GRID_DIMENSIONS.1);
+    let background = Grid::new_noise(
+        GRID_DIMENSIONS.0,
+        GRID_DIMENSIONS.1,
+        NoiseParams {
+            seed: 0,
+            frequency: 0.02,
+            octaves: 4,
+            lacunarity: 2.0,
+            persistence: 0.5,
+            channel_seeds: None,
+        },
+    );
     println!(
         "Grid size: {}x{} = {} cells",
         grid.width(),
         grid.height(),
         grid.width() * grid.height()
     );
-    let square = create_grid_texture(&grid, &mut meshes, &mut materials, &mut images);
+    let lenia = Lenia::new(
+        Kernel::gauss7(),
+        LeniaParams {
+            mu: 0.15,
+            sigma: 0.015,
+            dt: 0.1,
+        },
+        Integrator::Euler,
+        Boundary::Wrap,
+    );
+    let world = World {
+        grid,
+        background,
+        lenia,
+        entity: None,
+    };
+    let square = create_grid_texture(&world.composited(), &mut meshes, &mut materials, &mut images);
     let entity = commands.spawn(square).id();
     commands.insert_resource(World {
-        grid: grid,
         entity: Some(entity),
+        ..world
     });
 }
 
@@ -122,9 +168,8 @@ fn handle_input(
     mut images: ResMut<Assets<Image>>,
 ) {
     if keys.just_pressed(KeyCode::Space) {
-        let kernel = Kernel::gauss7();
-        world.grid.convolve(&kernel, ParConvolver);
-        let square = create_grid_texture(&world.grid, &mut meshes, &mut materials, &mut images);
+        world.lenia.step(&mut world.grid);
+        let square = create_grid_texture(&world.composited(), &mut meshes, &mut materials, &mut images);
         let entity = world.entity.unwrap();
         commands.entity(entity).insert(square);
     }
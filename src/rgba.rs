@@ -1,56 +1,19 @@
 use crate::grid::*;
 use crate::kernel::*;
-use rand::{Rng, SeedableRng};
-use rand_chacha::ChaCha8Rng;
 use rayon::prelude::*;
 use rustfft::{num_complex::Complex, num_traits::Zero, FftPlanner};
-use std::{fmt::Debug, time::Instant};
-
-#[derive(Clone, Copy, Debug)]
-pub struct RGBA {
-    pub r: f32,
-    pub g: f32,
-    pub b: f32,
-    pub a: f32,
-}
-impl RGBA {
-    const ZERO: RGBA = RGBA {
-        r: 0.0,
-        g: 0.0,
-        b: 0.0,
-        a: 0.0,
-    };
-}
-
-impl Grid<RGBA> {
-    pub fn new_random(width: usize, height: usize) -> Grid<RGBA> {
-        let mut rng = ChaCha8Rng::from_seed([0; 32]);
-        let mut grid = Grid {
-            raster: Raster { width, height },
-            cells: vec![RGBA::ZERO; width * height],
-        };
-
-        grid.for_each_cell_mut(|_, _, _, cell| {
-            cell.r = rng.gen_range(0..255) as f32 / 255.0;
-            cell.g = rng.gen_range(0..255) as f32 / 255.0;
-            cell.b = rng.gen_range(0..255) as f32 / 255.0;
-            cell.a = 1.0;
-        });
-
-        grid
-    }
-}
+use wide::f32x4;
 
 impl Convolver<RGBA> for SimpleConvolver {
     #[inline(always)]
-    fn convolve(&self, grid: &mut Grid<RGBA>, kernel: &Kernel) {
+    fn convolve(&self, grid: &mut Grid<RGBA>, kernel: &Kernel, boundary: Boundary) {
         let width = grid.width();
         let height = grid.height();
         let mut new_cells = vec![RGBA::ZERO; width * height];
         for y in 0..height {
             for x in 0..width {
                 let mut new_cell = RGBA::ZERO;
-                convolve_kernel!(grid, kernel, x, y, new_cell);
+                convolve_kernel!(grid, kernel, x, y, boundary, new_cell);
                 let index = grid.index(x, y);
                 new_cells[index] = new_cell;
             }
@@ -61,7 +24,7 @@ impl Convolver<RGBA> for SimpleConvolver {
 
 impl Convolver<RGBA> for ParConvolver {
     #[inline(always)]
-    fn convolve(&self, grid: &mut Grid<RGBA>, kernel: &Kernel) {
+    fn convolve(&self, grid: &mut Grid<RGBA>, kernel: &Kernel, boundary: Boundary) {
         let width = grid.width();
         let height = grid.height();
         let mut new_cells = vec![RGBA::ZERO; width * height];
@@ -73,7 +36,7 @@ impl Convolver<RGBA> for ParConvolver {
                 for x in 0..width {
                     let mut new_cell = RGBA::ZERO;
 
-                    convolve_kernel!(grid, kernel, x, y, new_cell);
+                    convolve_kernel!(grid, kernel, x, y, boundary, new_cell);
 
                     row[x] = RGBA {
                         r: new_cell.r.clamp(0.0, 1.0),
@@ -88,54 +51,162 @@ impl Convolver<RGBA> for ParConvolver {
     }
 }
 
+pub struct SimdConvolver;
+
+impl Convolver<RGBA> for SimdConvolver {
+    #[inline(always)]
+    fn convolve(&self, grid: &mut Grid<RGBA>, kernel: &Kernel, boundary: Boundary) {
+        let width = grid.width();
+        let height = grid.height();
+        let mut new_cells = vec![RGBA::ZERO; width * height];
+        let kc = kernel.center();
+
+        new_cells
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for x in 0..width {
+                    let mut acc = f32x4::ZERO;
+                    for ky in 0..kernel.height() {
+                        for kx in 0..kernel.width() {
+                            let dx = x as isize + kx as isize - kc.x as isize;
+                            let dy = y as isize + ky as isize - kc.y as isize;
+                            let Some((rx, ry)) = grid.raster.resolve(dx, dy, boundary) else {
+                                continue;
+                            };
+                            let neighbour = grid.get(rx, ry);
+                            let weight = kernel.cells[ky * kernel.width() + kx];
+                            let lane = f32x4::new([neighbour.r, neighbour.g, neighbour.b, neighbour.a]);
+                            acc = lane.mul_add(f32x4::splat(weight), acc);
+                        }
+                    }
+                    let packed = acc.to_array();
+                    row[x] = RGBA {
+                        r: packed[0].clamp(0.0, 1.0),
+                        g: packed[1].clamp(0.0, 1.0),
+                        b: packed[2].clamp(0.0, 1.0),
+                        a: packed[3].clamp(0.0, 1.0),
+                    };
+                }
+            });
+
+        grid.cells = new_cells;
+    }
+}
+
 impl Convolver<RGBA> for FftConvolver {
     #[inline(always)]
-    fn convolve(&self, grid: &mut Grid<RGBA>, kernel: &Kernel) {
+    fn convolve(&self, grid: &mut Grid<RGBA>, kernel: &Kernel, boundary: Boundary) {
+        match boundary {
+            Boundary::Wrap => Self::convolve_wrap(grid, kernel),
+            _ => Self::convolve_padded(grid, kernel, boundary),
+        }
+    }
+}
+
+impl FftConvolver {
+    fn convolve_wrap(grid: &mut Grid<RGBA>, kernel: &Kernel) {
+        let (width, height) = (grid.width(), grid.height());
+        let size = width * height;
+        let mut planner = FftPlanner::new();
+        let fft = planner.plan_fft_forward(size);
+        let ifft = planner.plan_fft_inverse(size);
+
+        let mut grid_r = vec![Complex::zero(); size];
+        let mut grid_g = vec![Complex::zero(); size];
+        let mut grid_b = vec![Complex::zero(); size];
+        let mut grid_a = vec![Complex::zero(); size];
+        let mut kernel_complex = vec![Complex::zero(); size];
+
+        grid.for_each_cell(|x, y, i, color| {
+            let _ = (x, y);
+            grid_r[i] = Complex::new(color.r, 0.0);
+            grid_g[i] = Complex::new(color.g, 0.0);
+            grid_b[i] = Complex::new(color.b, 0.0);
+            grid_a[i] = Complex::new(color.a, 0.0);
+        });
+
+        let kc = kernel.center();
+        kernel.for_each_cell(|kx, ky, _, weight| {
+            let ox = (kx as isize - kc.x as isize).rem_euclid(width as isize) as usize;
+            let oy = (ky as isize - kc.y as isize).rem_euclid(height as isize) as usize;
+            kernel_complex[oy * width + ox] += Complex::new(*weight, 0.0);
+        });
+
+        fft.process(&mut grid_r);
+        fft.process(&mut grid_g);
+        fft.process(&mut grid_b);
+        fft.process(&mut grid_a);
+        fft.process(&mut kernel_complex);
+
+        for i in 0..size {
+            grid_r[i] *= kernel_complex[i];
+            grid_g[i] *= kernel_complex[i];
+            grid_b[i] *= kernel_complex[i];
+            grid_a[i] *= kernel_complex[i];
+        }
+
+        ifft.process(&mut grid_r);
+        ifft.process(&mut grid_g);
+        ifft.process(&mut grid_b);
+        ifft.process(&mut grid_a);
+
+        grid.for_each_cell_mut(|_, _, i, cell| {
+            cell.r = (grid_r[i].re / size as f32).clamp(0.0, 1.0);
+            cell.g = (grid_g[i].re / size as f32).clamp(0.0, 1.0);
+            cell.b = (grid_b[i].re / size as f32).clamp(0.0, 1.0);
+            cell.a = (grid_a[i].re / size as f32).clamp(0.0, 1.0);
+        });
+    }
+
+    fn convolve_padded(grid: &mut Grid<RGBA>, kernel: &Kernel, boundary: Boundary) {
         let (grid_width, grid_height) = (grid.width(), grid.height());
         let (kernel_width, kernel_height) = (kernel.width(), kernel.height());
         let padded_width = grid_width + kernel_width - 1;
         let padded_height = grid_height + kernel_height - 1;
-        let time = Instant::now();
         let mut planner = FftPlanner::new();
-        println!("Planner took {:?}", time.elapsed());
-        let time = Instant::now();
         let fft = planner.plan_fft_forward(padded_width * padded_height);
-        println!("FFT forward took {:?}", time.elapsed());
-        let time = Instant::now();
         let ifft = planner.plan_fft_inverse(padded_width * padded_height);
-        println!("FFT inverse took {:?}", time.elapsed());
 
         let padded_raster = Raster {
             width: padded_width,
             height: padded_height,
         };
 
-        let time = Instant::now();
         let mut grid_r = vec![Complex::zero(); padded_width * padded_height];
         let mut grid_g = vec![Complex::zero(); padded_width * padded_height];
         let mut grid_b = vec![Complex::zero(); padded_width * padded_height];
         let mut grid_a = vec![Complex::zero(); padded_width * padded_height];
         let mut kernel_complex = vec![Complex::zero(); padded_width * padded_height];
 
-        grid.for_each_cell(|x, y, _, color| {
-            let padded_index = y * padded_width + x;
-            grid_r[padded_index] = Complex::new(color.r, 0.0);
-            grid_g[padded_index] = Complex::new(color.g, 0.0);
-            grid_b[padded_index] = Complex::new(color.b, 0.0);
-            grid_a[padded_index] = Complex::new(color.a, 0.0);
-        });
+        for py in 0..padded_height {
+            for px in 0..padded_width {
+                let padded_index = py * padded_width + px;
+                let source = if px < grid_width && py < grid_height {
+                    Some((px, py))
+                } else if boundary == Boundary::Zero {
+                    None
+                } else {
+                    grid.raster.resolve(px as isize, py as isize, boundary)
+                };
+                if let Some((sx, sy)) = source {
+                    let color = grid.get(sx, sy);
+                    grid_r[padded_index] = Complex::new(color.r, 0.0);
+                    grid_g[padded_index] = Complex::new(color.g, 0.0);
+                    grid_b[padded_index] = Complex::new(color.b, 0.0);
+                    grid_a[padded_index] = Complex::new(color.a, 0.0);
+                }
+            }
+        }
         kernel.for_each_cell(|x, y, _, cell| {
             let padded_index = y * padded_width + x;
             kernel_complex[padded_index] = Complex::new(*cell, 0.0);
         });
-        println!("Preparation took {:?}", time.elapsed());
-        let time = Instant::now();
         fft.process(&mut grid_r);
         fft.process(&mut grid_g);
         fft.process(&mut grid_b);
         fft.process(&mut grid_a);
         fft.process(&mut kernel_complex);
-        println!("FFT processing took {:?}", time.elapsed());
 
         padded_raster.for_each(|_, _, i| {
             grid_r[i] *= kernel_complex[i];
@@ -144,12 +215,10 @@ impl Convolver<RGBA> for FftConvolver {
             grid_a[i] *= kernel_complex[i];
         });
 
-        let time = Instant::now();
         ifft.process(&mut grid_r);
         ifft.process(&mut grid_g);
         ifft.process(&mut grid_b);
         ifft.process(&mut grid_a);
-        println!("IFFT processing took {:?}", time.elapsed());
 
         let kc = kernel.center();
         grid.for_each_cell_mut(|x, y, _, cell| {
@@ -168,3 +237,43 @@ impl Convolver<RGBA> for FftConvolver {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_grid() -> Grid<RGBA> {
+        let cells = (0..16)
+            .map(|i| {
+                let v = i as f32 / 16.0;
+                RGBA {
+                    r: v,
+                    g: v * 0.5,
+                    b: 1.0 - v,
+                    a: 1.0,
+                }
+            })
+            .collect();
+        Grid {
+            raster: Raster { width: 4, height: 4 },
+            cells,
+        }
+    }
+
+    #[test]
+    fn simd_convolve_agrees_with_scalar_convolve() {
+        let kernel = Kernel::gauss3();
+        let mut scalar_grid = test_grid();
+        let mut simd_grid = test_grid();
+
+        SimpleConvolver.convolve(&mut scalar_grid, &kernel, Boundary::Wrap);
+        SimdConvolver.convolve(&mut simd_grid, &kernel, Boundary::Wrap);
+
+        for (scalar, simd) in scalar_grid.cells.iter().zip(simd_grid.cells.iter()) {
+            assert!((scalar.r - simd.r).abs() < 1e-5);
+            assert!((scalar.g - simd.g).abs() < 1e-5);
+            assert!((scalar.b - simd.b).abs() < 1e-5);
+            assert!((scalar.a - simd.a).abs() < 1e-5);
+        }
+    }
+}